@@ -2,6 +2,37 @@ use safetensors::Dtype as RDtype;
 
 use crate::ffi::Dtype;
 
+/// Number of bits a single element of `dtype` occupies when packed, per the
+/// OCP Microscaling spec for the sub-byte MX formats and the natural width
+/// for every other dtype.
+pub fn bits_per_element(dtype: RDtype) -> usize {
+    match dtype {
+        RDtype::F4 => 4,
+        RDtype::F6_E2M3 | RDtype::F6_E3M2 => 6,
+        RDtype::BOOL | RDtype::U8 | RDtype::I8 | RDtype::F8_E5M2 | RDtype::F8_E4M3 | RDtype::F8_E8M0 => 8,
+        RDtype::I16 | RDtype::U16 | RDtype::F16 | RDtype::BF16 => 16,
+        RDtype::I32 | RDtype::U32 | RDtype::F32 => 32,
+        RDtype::F64 | RDtype::I64 | RDtype::U64 => 64,
+        // `RDtype` is non-exhaustive upstream, but every variant it currently
+        // defines is matched above.
+        _ => unreachable!("unhandled safetensors::Dtype variant: {dtype:?}"),
+    }
+}
+
+/// Size in bytes of a single element of `dtype`, for dtypes that are
+/// individually byte-addressable.
+///
+/// Returns `None` for the sub-byte MX formats (`F4`, `F6_E2M3`, `F6_E3M2`),
+/// whose elements are only addressable once packed alongside their
+/// neighbours. The `safetensors` crate itself handles packing those into
+/// whole bytes (`shape` stays logical; the data is
+/// `ceil(elements * bits_per_element / 8)` bytes), so this crate never
+/// needs to pack or unpack a shape by hand.
+pub fn byte_size(dtype: RDtype) -> Option<usize> {
+    let bits = bits_per_element(dtype);
+    bits.is_multiple_of(8).then_some(bits / 8)
+}
+
 // Upload: Rust -> Cxx
 impl Into<Dtype> for RDtype {
     fn into(self) -> Dtype {
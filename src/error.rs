@@ -0,0 +1,37 @@
+use safetensors::SafeTensorError;
+use std::fmt;
+
+/// Errors surfaced across the cxx bridge.
+///
+/// This wraps [`SafeTensorError`] from the underlying `safetensors` crate
+/// together with validation failures that only make sense on the bridge
+/// side (bad slice bounds, duplicate tensor names, ...), so both can be
+/// reported through the same `Result` without losing their message.
+#[derive(Debug)]
+pub enum Error {
+    SafeTensor(SafeTensorError),
+    /// `starts`/`stops` did not describe a valid slice of the tensor.
+    InvalidSlice(String),
+    /// Two entries in the data passed to `serialize` shared the same name.
+    DuplicateTensorName(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SafeTensor(err) => write!(f, "{err}"),
+            Error::InvalidSlice(msg) => write!(f, "invalid slice: {msg}"),
+            Error::DuplicateTensorName(name) => {
+                write!(f, "duplicate tensor name: {name}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<SafeTensorError> for Error {
+    fn from(err: SafeTensorError) -> Self {
+        Error::SafeTensor(err)
+    }
+}
@@ -0,0 +1,252 @@
+use crate::conversion::byte_size;
+use crate::error::Error;
+use crate::ffi::{ByteRange, PairStrStr, TensorView};
+use memmap2::{Mmap, MmapOptions};
+use safetensors::tensor::Metadata;
+use safetensors::SafeTensorError;
+use std::fs::File;
+
+/// A memory-mapped, lazily-read `safetensors` file.
+///
+/// Unlike [`deserialize`](crate::deserialize), which copies the full header
+/// and hands back a `Vec` of tensor views eagerly, [`open`] only parses the
+/// header up front. The tensor payload stays mapped and is faulted in by
+/// the OS on demand as individual tensors are accessed through the handle.
+pub struct SafeOpen {
+    mmap: Mmap,
+    metadata: Metadata,
+    data_offset: usize,
+}
+
+/// Opens `path` as a memory-mapped `safetensors` file.
+///
+/// Only the JSON header is parsed eagerly; tensor data remains mapped and
+/// is paged in lazily as it is read through the returned handle.
+pub fn open(path: &str) -> Result<Box<SafeOpen>, SafeTensorError> {
+    let file = File::open(path).map_err(SafeTensorError::IoError)?;
+    // Safety: the mapping is only ever read through `SafeOpen`; concurrent
+    // external modification of the underlying file is the caller's
+    // responsibility, same as any other mmap-based reader.
+    let mmap = unsafe { MmapOptions::new().map(&file) }.map_err(SafeTensorError::IoError)?;
+    let (n, metadata) = safetensors::SafeTensors::read_metadata(&mmap)?;
+    Ok(Box::new(SafeOpen {
+        mmap,
+        metadata,
+        data_offset: 8 + n,
+    }))
+}
+
+impl SafeOpen {
+    /// Lists the tensor names stored in the file, without loading any
+    /// tensor data.
+    pub fn keys(&self) -> Vec<String> {
+        self.metadata.tensors().keys().cloned().collect()
+    }
+
+    /// Returns whether `name` names a tensor in the file.
+    pub fn has_tensor(&self, name: &str) -> bool {
+        self.metadata.info(name).is_some()
+    }
+
+    /// Fetches a single tensor by name.
+    ///
+    /// The returned [`TensorView`] borrows directly from the mapped file,
+    /// so only the pages backing this one tensor are ever faulted in; the
+    /// rest of the file is left untouched.
+    ///
+    /// # Safety
+    ///
+    /// Marked `unsafe` only because the returned view's lifetime is tied to
+    /// `self` rather than to `name`, which cxx requires spelling out
+    /// explicitly; the body performs no unsafe operations.
+    pub unsafe fn get_tensor<'a>(&'a self, name: &str) -> Result<TensorView<'a>, SafeTensorError> {
+        let info = self
+            .metadata
+            .info(name)
+            .ok_or_else(|| SafeTensorError::TensorNotFound(name.to_string()))?;
+        let start = self.data_offset + info.data_offsets.0;
+        let end = self.data_offset + info.data_offsets.1;
+        Ok(TensorView {
+            shape: info.shape.clone(),
+            dtype: info.dtype.into(),
+            data: &self.mmap[start..end],
+            data_len: info.data_offsets.1 - info.data_offsets.0,
+        })
+    }
+
+    /// Returns the free-form string metadata stored alongside the tensors.
+    pub fn get_metadata(&self) -> Vec<PairStrStr> {
+        let Some(metadata) = self.metadata.metadata() else {
+            return Vec::new();
+        };
+        metadata
+            .iter()
+            .map(|(key, value)| PairStrStr {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect()
+    }
+
+    /// Reads a `starts..stops` sub-region of a tensor without copying the
+    /// rest of it, e.g. one attention head or one row block of an embedding
+    /// matrix.
+    ///
+    /// Succeeds only when the requested region is contiguous in the
+    /// underlying file, which holds e.g. when only the outermost dimension
+    /// is sliced and every other one is selected in full, or when every
+    /// dimension but the innermost selects a single index. A full outer
+    /// dimension followed by a partially selected inner one is not
+    /// contiguous. For a region scattered
+    /// across several runs, use [`SafeOpen::get_slice_ranges`] instead and
+    /// gather them on the caller's side.
+    ///
+    /// # Safety
+    ///
+    /// Marked `unsafe` only because the returned view's lifetime is tied to
+    /// `self` rather than to `name`, which cxx requires spelling out
+    /// explicitly; the body performs no unsafe operations.
+    pub unsafe fn get_slice<'a>(
+        &'a self,
+        name: &str,
+        starts: Vec<usize>,
+        stops: Vec<usize>,
+    ) -> Result<TensorView<'a>, Error> {
+        let info = self
+            .metadata
+            .info(name)
+            .ok_or_else(|| SafeTensorError::TensorNotFound(name.to_string()))?;
+        let ranges = self.slice_byte_ranges(name, info, &starts, &stops)?;
+        if ranges.is_empty() {
+            let shape: Vec<usize> = starts.iter().zip(&stops).map(|(s, e)| e - s).collect();
+            return Ok(TensorView {
+                shape,
+                dtype: info.dtype.into(),
+                data: &[],
+                data_len: 0,
+            });
+        }
+        if ranges.len() != 1 {
+            return Err(Error::InvalidSlice(format!(
+                "{name}[{starts:?}..{stops:?}] is not contiguous ({} runs); use get_slice_ranges instead",
+                ranges.len()
+            )));
+        }
+        let start = self.data_offset + info.data_offsets.0 + ranges[0].0;
+        let end = self.data_offset + info.data_offsets.0 + ranges[0].1;
+        let shape: Vec<usize> = starts.iter().zip(&stops).map(|(s, e)| e - s).collect();
+        Ok(TensorView {
+            shape,
+            dtype: info.dtype.into(),
+            data: &self.mmap[start..end],
+            data_len: end - start,
+        })
+    }
+
+    /// Computes the contiguous byte ranges, relative to the start of this
+    /// tensor's own data, that together make up the `starts..stops` slice.
+    ///
+    /// Unlike [`SafeOpen::get_slice`], this always succeeds for a
+    /// dimension-matched, in-bounds slice of a tensor whose dtype is a
+    /// whole number of bytes, at the cost of possibly returning more than
+    /// one run for the caller to gather. Tensors of the sub-byte MX dtypes
+    /// (`F4`, `F6_E2M3`, `F6_E3M2`) cannot be sliced and return an error.
+    pub fn get_slice_ranges(
+        &self,
+        name: &str,
+        starts: Vec<usize>,
+        stops: Vec<usize>,
+    ) -> Result<Vec<ByteRange>, Error> {
+        let info = self
+            .metadata
+            .info(name)
+            .ok_or_else(|| SafeTensorError::TensorNotFound(name.to_string()))?;
+        let ranges = self.slice_byte_ranges(name, info, &starts, &stops)?;
+        Ok(ranges
+            .into_iter()
+            .map(|(start, end)| ByteRange { start, end })
+            .collect())
+    }
+
+    fn slice_byte_ranges(
+        &self,
+        name: &str,
+        info: &safetensors::tensor::TensorInfo,
+        starts: &[usize],
+        stops: &[usize],
+    ) -> Result<Vec<(usize, usize)>, Error> {
+        let shape = &info.shape;
+        if starts.len() != shape.len() || stops.len() != shape.len() {
+            return Err(Error::InvalidSlice(format!(
+                "{name} has {} dimensions, got {} starts and {} stops",
+                shape.len(),
+                starts.len(),
+                stops.len()
+            )));
+        }
+        for (dim, ((&start, &stop), &size)) in starts.iter().zip(stops).zip(shape).enumerate() {
+            if start > stop || stop > size {
+                return Err(Error::InvalidSlice(format!(
+                    "{name}: dimension {dim} range {start}..{stop} is out of bounds for size {size}"
+                )));
+            }
+        }
+        let Some(itemsize) = byte_size(info.dtype) else {
+            return Err(Error::InvalidSlice(format!(
+                "{name} has a sub-byte packed dtype and cannot be sliced at byte granularity"
+            )));
+        };
+
+        // An empty dimension (start == stop) selects nothing at all.
+        if starts.iter().zip(stops).any(|(s, e)| s == e) {
+            return Ok(Vec::new());
+        }
+
+        let ndim = shape.len();
+        let mut strides = vec![1usize; ndim];
+        for dim in (0..ndim.saturating_sub(1)).rev() {
+            strides[dim] = strides[dim + 1] * shape[dim + 1];
+        }
+
+        // Trailing dimensions selected in full fold into one contiguous
+        // inner block; only the dimensions before them need enumerating.
+        let mut k = ndim;
+        while k > 0 && starts[k - 1] == 0 && stops[k - 1] == shape[k - 1] {
+            k -= 1;
+        }
+        let inner_elems: usize = shape[k..].iter().product();
+
+        let mut ranges = Vec::new();
+        let mut idx = starts[..k].to_vec();
+        'outer: loop {
+            let offset: usize = idx.iter().zip(&strides[..k]).map(|(i, s)| i * s).sum();
+            ranges.push((offset * itemsize, (offset + inner_elems) * itemsize));
+
+            let mut pos = k;
+            loop {
+                if pos == 0 {
+                    break 'outer;
+                }
+                pos -= 1;
+                idx[pos] += 1;
+                if idx[pos] < stops[pos] {
+                    continue 'outer;
+                }
+                idx[pos] = starts[pos];
+            }
+        }
+
+        // The odometer above emits runs in increasing offset order, so a
+        // genuinely contiguous slice (e.g. a row block of an embedding
+        // matrix) shows up as a sequence of back-to-back runs; coalesce
+        // them into the fewest runs that describe the same bytes.
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if last.1 == start => last.1 = end,
+                _ => merged.push((start, end)),
+            }
+        }
+        Ok(merged)
+    }
+}